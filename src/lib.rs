@@ -4,6 +4,8 @@ extern crate diesel;
 #[cfg(test)]
 mod tests;
 
+pub mod path;
+
 pub mod sql_types {
     use diesel::query_builder::QueryId;
     use diesel::sql_types::SqlType;
@@ -31,12 +33,37 @@ pub mod values {
     use diesel::pg::Pg;
     use diesel::sql_types::Text;
 
+    /// The version byte that prefixes the binary wire format shared by
+    /// `ltree`, `lquery` and `ltxtquery`.
+    const WIRE_FORMAT_VERSION: i8 = 1;
+
+    fn check_wire_format_version(version: i8) -> deserialize::Result<()> {
+        if version != WIRE_FORMAT_VERSION {
+            return Err(format!(
+                "Unsupported ltree/lquery/ltxtquery binary protocol version: {}",
+                version
+            )
+            .into());
+        }
+        Ok(())
+    }
+
     #[derive(Debug, PartialEq, Clone, FromSqlRow, AsExpression)]
     #[diesel(sql_type = crate::sql_types::Ltree)]
+    #[diesel(sql_type = diesel::sql_types::Nullable<crate::sql_types::Ltree>)]
     pub struct Ltree(pub String);
 
+    impl Ltree {
+        /// Parses this value into a [`crate::path::LtreePath`] for in-memory
+        /// manipulation, without a database round trip.
+        pub fn path(&self) -> Result<crate::path::LtreePath, crate::path::LtreePathError> {
+            crate::path::LtreePath::new(&self.0)
+        }
+    }
+
     impl diesel::serialize::ToSql<crate::sql_types::Ltree, Pg> for Ltree {
         fn to_sql(&self, out: &mut diesel::serialize::Output<Pg>) -> diesel::serialize::Result {
+            out.write_all(&[WIRE_FORMAT_VERSION as u8])?;
             out.write_all(self.0.as_bytes())?;
             Ok(diesel::serialize::IsNull::No)
         }
@@ -47,7 +74,7 @@ pub mod values {
             let mut raw = value.as_bytes();
 
             let version = raw.read_i8()?;
-            debug_assert_eq!(version, 1, "Unknown ltree binary protocol version.");
+            check_wire_format_version(version)?;
 
             let mut buf = String::new();
             raw.read_to_string(&mut buf)?;
@@ -79,6 +106,108 @@ pub mod values {
             String::from_sql(value).map(Ltree)
         }
     }
+
+    #[derive(Debug, PartialEq, Clone, FromSqlRow, AsExpression)]
+    #[diesel(sql_type = crate::sql_types::Lquery)]
+    #[diesel(sql_type = diesel::sql_types::Nullable<crate::sql_types::Lquery>)]
+    pub struct Lquery(pub String);
+
+    impl diesel::serialize::ToSql<crate::sql_types::Lquery, Pg> for Lquery {
+        fn to_sql(&self, out: &mut diesel::serialize::Output<Pg>) -> diesel::serialize::Result {
+            out.write_all(&[WIRE_FORMAT_VERSION as u8])?;
+            out.write_all(self.0.as_bytes())?;
+            Ok(diesel::serialize::IsNull::No)
+        }
+    }
+
+    impl diesel::deserialize::FromSql<crate::sql_types::Lquery, Pg> for Lquery {
+        fn from_sql(value: diesel::pg::PgValue<'_>) -> deserialize::Result<Self> {
+            let mut raw = value.as_bytes();
+
+            let version = raw.read_i8()?;
+            check_wire_format_version(version)?;
+
+            let mut buf = String::new();
+            raw.read_to_string(&mut buf)?;
+            Ok(Lquery(buf))
+        }
+    }
+
+    impl<DB> diesel::serialize::ToSql<Text, DB> for Lquery
+    where
+        String: diesel::serialize::ToSql<Text, DB>,
+        DB: diesel::backend::Backend,
+        DB: diesel::sql_types::HasSqlType<crate::sql_types::Lquery>,
+    {
+        fn to_sql<'b>(
+            &'b self,
+            out: &mut diesel::serialize::Output<'b, '_, DB>,
+        ) -> diesel::serialize::Result {
+            self.0.to_sql(out)
+        }
+    }
+
+    impl<DB> diesel::deserialize::FromSql<Text, DB> for Lquery
+    where
+        String: diesel::deserialize::FromSql<Text, DB>,
+        DB: diesel::backend::Backend,
+        DB: diesel::sql_types::HasSqlType<crate::sql_types::Lquery>,
+    {
+        fn from_sql(value: diesel::backend::RawValue<DB>) -> deserialize::Result<Self> {
+            String::from_sql(value).map(Lquery)
+        }
+    }
+
+    #[derive(Debug, PartialEq, Clone, FromSqlRow, AsExpression)]
+    #[diesel(sql_type = crate::sql_types::Ltxtquery)]
+    #[diesel(sql_type = diesel::sql_types::Nullable<crate::sql_types::Ltxtquery>)]
+    pub struct Ltxtquery(pub String);
+
+    impl diesel::serialize::ToSql<crate::sql_types::Ltxtquery, Pg> for Ltxtquery {
+        fn to_sql(&self, out: &mut diesel::serialize::Output<Pg>) -> diesel::serialize::Result {
+            out.write_all(&[WIRE_FORMAT_VERSION as u8])?;
+            out.write_all(self.0.as_bytes())?;
+            Ok(diesel::serialize::IsNull::No)
+        }
+    }
+
+    impl diesel::deserialize::FromSql<crate::sql_types::Ltxtquery, Pg> for Ltxtquery {
+        fn from_sql(value: diesel::pg::PgValue<'_>) -> deserialize::Result<Self> {
+            let mut raw = value.as_bytes();
+
+            let version = raw.read_i8()?;
+            check_wire_format_version(version)?;
+
+            let mut buf = String::new();
+            raw.read_to_string(&mut buf)?;
+            Ok(Ltxtquery(buf))
+        }
+    }
+
+    impl<DB> diesel::serialize::ToSql<Text, DB> for Ltxtquery
+    where
+        String: diesel::serialize::ToSql<Text, DB>,
+        DB: diesel::backend::Backend,
+        DB: diesel::sql_types::HasSqlType<crate::sql_types::Ltxtquery>,
+    {
+        fn to_sql<'b>(
+            &'b self,
+            out: &mut diesel::serialize::Output<'b, '_, DB>,
+        ) -> diesel::serialize::Result {
+            self.0.to_sql(out)
+        }
+    }
+
+    impl<DB> diesel::deserialize::FromSql<Text, DB> for Ltxtquery
+    where
+        String: diesel::deserialize::FromSql<Text, DB>,
+        DB: diesel::backend::Backend,
+        DB: diesel::sql_types::HasSqlType<crate::sql_types::Ltxtquery>,
+    {
+        fn from_sql(value: diesel::backend::RawValue<DB>) -> deserialize::Result<Self> {
+            String::from_sql(value).map(Ltxtquery)
+        }
+    }
 }
 
 mod functions {
@@ -102,7 +231,7 @@ mod functions {
 mod dsl {
     use crate::sql_types::*;
     use diesel::expression::{AsExpression, Expression};
-    use diesel::sql_types::Array;
+    use diesel::sql_types::{Array, Text};
 
     mod predicates {
         use crate::sql_types::*;
@@ -166,6 +295,19 @@ mod dsl {
         fn concat<T: AsExpression<Ltree>>(self, other: T) -> Concat<Self, T::Expression> {
             Concat::new(self, other.as_expression())
         }
+
+        fn concat_text<T: AsExpression<Text>>(self, other: T) -> Concat<Self, T::Expression> {
+            Concat::new(self, other.as_expression())
+        }
+    }
+
+    /// `text || ltree`, the mirror image of [`LtreeExtensions::concat_text`].
+    pub fn text_concat_ltree<L, R>(text: L, ltree: R) -> Concat<L::Expression, R::Expression>
+    where
+        L: AsExpression<Text>,
+        R: AsExpression<Ltree>,
+    {
+        Concat::new(text.as_expression(), ltree.as_expression())
     }
 
     pub trait LtreeArrayExtensions: Expression<SqlType = Array<Ltree>> + Sized {
@@ -271,8 +413,139 @@ mod dsl {
     impl<T: Expression<SqlType = Lquery>> LqueryExtensions for T {}
     impl<T: Expression<SqlType = Array<Lquery>>> LqueryArrayExtensions for T {}
     impl<T: Expression<SqlType = Ltxtquery>> LtxtqueryExtensions for T {}
+
+    mod array_comparison {
+        use crate::sql_types::*;
+        use diesel::expression::{AppearsOnTable, AsExpression, Expression, SelectableExpression};
+        use diesel::pg::Pg;
+        use diesel::query_builder::{AstPass, QueryFragment, QueryId};
+        use diesel::query_source::QuerySource;
+        use diesel::sql_types::Array;
+        use std::marker::PhantomData;
+
+        /// Sealed marker for the ltree SQL types that can appear as the
+        /// element type of an `any`/`all` array comparison.
+        pub trait ArrayComparable: diesel::sql_types::SqlType {}
+
+        impl ArrayComparable for Ltree {}
+        impl ArrayComparable for Lquery {}
+        impl ArrayComparable for Ltxtquery {}
+
+        #[derive(Debug, Clone, Copy, QueryId)]
+        pub struct Any<ST, Expr> {
+            expr: Expr,
+            _marker: PhantomData<ST>,
+        }
+
+        impl<ST, Expr> Expression for Any<ST, Expr>
+        where
+            ST: ArrayComparable,
+            Expr: Expression<SqlType = Array<ST>>,
+        {
+            type SqlType = ST;
+        }
+
+        impl<ST, Expr> QueryFragment<Pg> for Any<ST, Expr>
+        where
+            Expr: QueryFragment<Pg>,
+        {
+            fn walk_ast<'b>(&'b self, mut out: AstPass<'_, 'b, Pg>) -> diesel::QueryResult<()> {
+                out.push_sql("ANY(");
+                self.expr.walk_ast(out.reborrow())?;
+                out.push_sql(")");
+                Ok(())
+            }
+        }
+
+        impl<ST, Expr, QS> SelectableExpression<QS> for Any<ST, Expr>
+        where
+            Self: AppearsOnTable<QS>,
+            Expr: SelectableExpression<QS>,
+            QS: QuerySource,
+        {
+        }
+
+        impl<ST, Expr, QS> AppearsOnTable<QS> for Any<ST, Expr>
+        where
+            Self: Expression,
+            Expr: AppearsOnTable<QS>,
+            QS: QuerySource,
+        {
+        }
+
+        #[derive(Debug, Clone, Copy, QueryId)]
+        pub struct All<ST, Expr> {
+            expr: Expr,
+            _marker: PhantomData<ST>,
+        }
+
+        impl<ST, Expr> Expression for All<ST, Expr>
+        where
+            ST: ArrayComparable,
+            Expr: Expression<SqlType = Array<ST>>,
+        {
+            type SqlType = ST;
+        }
+
+        impl<ST, Expr> QueryFragment<Pg> for All<ST, Expr>
+        where
+            Expr: QueryFragment<Pg>,
+        {
+            fn walk_ast<'b>(&'b self, mut out: AstPass<'_, 'b, Pg>) -> diesel::QueryResult<()> {
+                out.push_sql("ALL(");
+                self.expr.walk_ast(out.reborrow())?;
+                out.push_sql(")");
+                Ok(())
+            }
+        }
+
+        impl<ST, Expr, QS> SelectableExpression<QS> for All<ST, Expr>
+        where
+            Self: AppearsOnTable<QS>,
+            Expr: SelectableExpression<QS>,
+            QS: QuerySource,
+        {
+        }
+
+        impl<ST, Expr, QS> AppearsOnTable<QS> for All<ST, Expr>
+        where
+            Self: Expression,
+            Expr: AppearsOnTable<QS>,
+            QS: QuerySource,
+        {
+        }
+
+        /// Wraps an array-valued expression so it can be used as `ANY(...)`
+        /// on the right-hand side of `matches`/`contains`/`tmatches`.
+        pub fn any<ST, T>(vals: T) -> Any<ST, T::Expression>
+        where
+            ST: ArrayComparable,
+            T: AsExpression<Array<ST>>,
+        {
+            Any {
+                expr: vals.as_expression(),
+                _marker: PhantomData,
+            }
+        }
+
+        /// Wraps an array-valued expression so it can be used as `ALL(...)`
+        /// on the right-hand side of `matches`/`contains`/`tmatches`.
+        pub fn all<ST, T>(vals: T) -> All<ST, T::Expression>
+        where
+            ST: ArrayComparable,
+            T: AsExpression<Array<ST>>,
+        {
+            All {
+                expr: vals.as_expression(),
+                _marker: PhantomData,
+            }
+        }
+    }
+
+    pub use self::array_comparison::{all, any, All, Any};
 }
 
 pub use crate::dsl::*;
 pub use crate::functions::*;
+pub use crate::path::{LtreePath, LtreePathError};
 pub use crate::values::*;
@@ -0,0 +1,211 @@
+//! Client-side `ltree` path manipulation.
+//!
+//! [`LtreePath`] mirrors the Postgres `ltree` algebra (`nlevel`, `subltree`,
+//! `subpath`, `index`, `lca`, concatenation) in pure Rust, so applications
+//! that build and compare hierarchy paths in memory only need to hit the
+//! database for storage and filtering.
+
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+/// The maximum length, in bytes, of a single `ltree` label.
+pub const MAX_LABEL_LEN: usize = 256;
+
+/// The maximum number of labels in an `ltree` path.
+pub const MAX_LABELS: usize = 65535;
+
+/// An error produced while validating or parsing an [`LtreePath`].
+#[derive(Debug, PartialEq, Eq)]
+pub enum LtreePathError {
+    /// A label was empty.
+    EmptyLabel,
+    /// A label exceeded [`MAX_LABEL_LEN`] bytes.
+    LabelTooLong(String),
+    /// A label contained a character other than `A-Za-z0-9_`.
+    InvalidCharacter(String),
+    /// The path contained more than [`MAX_LABELS`] labels.
+    TooManyLabels(usize),
+}
+
+impl fmt::Display for LtreePathError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LtreePathError::EmptyLabel => write!(f, "ltree labels must not be empty"),
+            LtreePathError::LabelTooLong(label) => write!(
+                f,
+                "ltree label {:?} is longer than the maximum of {} bytes",
+                label, MAX_LABEL_LEN
+            ),
+            LtreePathError::InvalidCharacter(label) => write!(
+                f,
+                "ltree label {:?} contains characters outside [A-Za-z0-9_]",
+                label
+            ),
+            LtreePathError::TooManyLabels(len) => write!(
+                f,
+                "ltree path has {} labels, which is more than the maximum of {}",
+                len, MAX_LABELS
+            ),
+        }
+    }
+}
+
+impl Error for LtreePathError {}
+
+/// A parsed `ltree` path, manipulated entirely in memory.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct LtreePath {
+    labels: Vec<String>,
+}
+
+impl LtreePath {
+    /// Parses a dotted `ltree` string, e.g. `"Top.Science.Astronomy"`,
+    /// validating each label against the rules Postgres enforces on the
+    /// `ltree` type.
+    pub fn new(path: &str) -> Result<Self, LtreePathError> {
+        let labels: Vec<String> = if path.is_empty() {
+            Vec::new()
+        } else {
+            path.split('.').map(str::to_owned).collect()
+        };
+
+        if labels.len() > MAX_LABELS {
+            return Err(LtreePathError::TooManyLabels(labels.len()));
+        }
+
+        for label in &labels {
+            validate_label(label)?;
+        }
+
+        Ok(LtreePath { labels })
+    }
+
+    /// An infallible iterator over this path's labels.
+    pub fn labels(&self) -> impl Iterator<Item = &str> {
+        self.labels.iter().map(String::as_str)
+    }
+
+    /// The number of labels in the path.
+    pub fn nlevel(&self) -> usize {
+        self.labels.len()
+    }
+
+    /// The subpath from `start` (inclusive) to `end` (exclusive).
+    ///
+    /// Indices are clamped to the length of the path, matching Postgres's
+    /// `subltree` behaviour.
+    pub fn subltree(&self, start: usize, end: usize) -> LtreePath {
+        let end = end.min(self.labels.len());
+        let start = start.min(end);
+        LtreePath {
+            labels: self.labels[start..end].to_vec(),
+        }
+    }
+
+    /// The subpath of `len` labels starting at `offset`.
+    ///
+    /// A negative `offset` counts from the end of the path. A negative `len`
+    /// takes labels up to that many positions before the end of the path.
+    pub fn subpath(&self, offset: isize, len: isize) -> LtreePath {
+        let start = resolve_offset(self.labels.len(), offset);
+        let end = if len < 0 {
+            (self.labels.len() as isize + len).max(start as isize) as usize
+        } else {
+            (start + len as usize).min(self.labels.len())
+        };
+        self.subltree(start, end)
+    }
+
+    /// The subpath from `offset` to the end of the path.
+    ///
+    /// A negative `offset` counts from the end of the path. This is the
+    /// single-argument form of `subpath`.
+    pub fn subpath_from(&self, offset: isize) -> LtreePath {
+        let start = resolve_offset(self.labels.len(), offset);
+        self.subltree(start, self.labels.len())
+    }
+
+    /// The position of `subpath` within this path, or `None` if it does not
+    /// occur.
+    pub fn index(&self, subpath: &LtreePath) -> Option<usize> {
+        if subpath.labels.is_empty() {
+            return Some(0);
+        }
+        if subpath.labels.len() > self.labels.len() {
+            return None;
+        }
+        self.labels
+            .windows(subpath.labels.len())
+            .position(|window| window == subpath.labels.as_slice())
+    }
+
+    /// The longest common ancestor of `paths`.
+    ///
+    /// Returns the empty path if `paths` is empty or the paths share no
+    /// common prefix.
+    pub fn lca(paths: &[LtreePath]) -> LtreePath {
+        let mut iter = paths.iter();
+        let first = match iter.next() {
+            Some(first) => first,
+            None => return LtreePath { labels: Vec::new() },
+        };
+
+        let mut common_len = first.labels.len();
+        for path in iter {
+            common_len = first
+                .labels
+                .iter()
+                .zip(path.labels.iter())
+                .take(common_len)
+                .take_while(|(a, b)| a == b)
+                .count();
+        }
+
+        LtreePath {
+            labels: first.labels[..common_len].to_vec(),
+        }
+    }
+
+    /// Appends `other`'s labels onto this path.
+    pub fn concat(&self, other: &LtreePath) -> LtreePath {
+        let mut labels = self.labels.clone();
+        labels.extend(other.labels.iter().cloned());
+        LtreePath { labels }
+    }
+}
+
+fn resolve_offset(len: usize, offset: isize) -> usize {
+    if offset < 0 {
+        len.saturating_sub(offset.unsigned_abs())
+    } else {
+        (offset as usize).min(len)
+    }
+}
+
+fn validate_label(label: &str) -> Result<(), LtreePathError> {
+    if label.is_empty() {
+        return Err(LtreePathError::EmptyLabel);
+    }
+    if label.len() > MAX_LABEL_LEN {
+        return Err(LtreePathError::LabelTooLong(label.to_owned()));
+    }
+    if !label.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        return Err(LtreePathError::InvalidCharacter(label.to_owned()));
+    }
+    Ok(())
+}
+
+impl fmt::Display for LtreePath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.labels.join("."))
+    }
+}
+
+impl FromStr for LtreePath {
+    type Err = LtreePathError;
+
+    fn from_str(path: &str) -> Result<Self, Self::Err> {
+        LtreePath::new(path)
+    }
+}
@@ -0,0 +1,350 @@
+use crate::path::{LtreePath, LtreePathError};
+
+table! {
+    use diesel::sql_types::*;
+    use crate::sql_types::*;
+
+    items (id) {
+        id -> Int4,
+        path -> Ltree,
+        query -> Lquery,
+        tquery -> Ltxtquery,
+    }
+}
+
+mod wire_format {
+    use crate::values::{Lquery, Ltree, Ltxtquery};
+    use diesel::deserialize::FromSql;
+    use diesel::pg::{Pg, PgValue};
+    use diesel::serialize::{Output, ToSql};
+
+    #[test]
+    fn ltree_round_trips_through_the_binary_wire_format() {
+        let value = Ltree("Top.Science".to_string());
+
+        let mut buffer = Output::test();
+        ToSql::<crate::sql_types::Ltree, Pg>::to_sql(&value, &mut buffer).unwrap();
+        let bytes = buffer.into_inner();
+
+        assert_eq!(bytes[0], 1);
+        assert_eq!(&bytes[1..], b"Top.Science");
+
+        let decoded =
+            <Ltree as FromSql<crate::sql_types::Ltree, Pg>>::from_sql(PgValue::for_test(&bytes))
+                .unwrap();
+        assert_eq!(decoded, value);
+    }
+
+    #[test]
+    fn lquery_round_trips_through_the_binary_wire_format() {
+        let value = Lquery("Top.*".to_string());
+
+        let mut buffer = Output::test();
+        ToSql::<crate::sql_types::Lquery, Pg>::to_sql(&value, &mut buffer).unwrap();
+        let bytes = buffer.into_inner();
+
+        let decoded =
+            <Lquery as FromSql<crate::sql_types::Lquery, Pg>>::from_sql(PgValue::for_test(&bytes))
+                .unwrap();
+        assert_eq!(decoded, value);
+    }
+
+    #[test]
+    fn ltxtquery_round_trips_through_the_binary_wire_format() {
+        let value = Ltxtquery("Astronomy & Astrophysics".to_string());
+
+        let mut buffer = Output::test();
+        ToSql::<crate::sql_types::Ltxtquery, Pg>::to_sql(&value, &mut buffer).unwrap();
+        let bytes = buffer.into_inner();
+
+        let decoded = <Ltxtquery as FromSql<crate::sql_types::Ltxtquery, Pg>>::from_sql(
+            PgValue::for_test(&bytes),
+        )
+        .unwrap();
+        assert_eq!(decoded, value);
+    }
+
+    #[test]
+    fn from_sql_rejects_an_unknown_version_byte() {
+        let bytes = [2u8, b'a'];
+        let result =
+            <Ltree as FromSql<crate::sql_types::Ltree, Pg>>::from_sql(PgValue::for_test(&bytes));
+        assert!(result.is_err());
+    }
+}
+
+#[test]
+fn parses_a_valid_path() {
+    let path = LtreePath::new("Top.Science.Astronomy").unwrap();
+    assert_eq!(
+        path.labels().collect::<Vec<_>>(),
+        vec!["Top", "Science", "Astronomy"]
+    );
+}
+
+#[test]
+fn parses_the_empty_path() {
+    let path = LtreePath::new("").unwrap();
+    assert_eq!(path.nlevel(), 0);
+    assert_eq!(path.labels().collect::<Vec<_>>(), Vec::<&str>::new());
+}
+
+#[test]
+fn rejects_empty_labels() {
+    assert_eq!(LtreePath::new("Top..Science"), Err(LtreePathError::EmptyLabel));
+}
+
+#[test]
+fn rejects_invalid_characters() {
+    assert_eq!(
+        LtreePath::new("Top.Science-Fiction"),
+        Err(LtreePathError::InvalidCharacter("Science-Fiction".to_string()))
+    );
+}
+
+#[test]
+fn rejects_labels_that_are_too_long() {
+    let label = "a".repeat(257);
+    assert_eq!(
+        LtreePath::new(&label),
+        Err(LtreePathError::LabelTooLong(label))
+    );
+}
+
+#[test]
+fn nlevel_counts_labels() {
+    let path = LtreePath::new("Top.Science.Astronomy").unwrap();
+    assert_eq!(path.nlevel(), 3);
+}
+
+#[test]
+fn subltree_slices_by_index_range() {
+    let path = LtreePath::new("Top.Science.Astronomy.Astrophysics").unwrap();
+    let sub = path.subltree(1, 3);
+    assert_eq!(sub.labels().collect::<Vec<_>>(), vec!["Science", "Astronomy"]);
+}
+
+#[test]
+fn subltree_clamps_out_of_range_indices() {
+    let path = LtreePath::new("Top.Science").unwrap();
+    let sub = path.subltree(1, 10);
+    assert_eq!(sub.labels().collect::<Vec<_>>(), vec!["Science"]);
+}
+
+#[test]
+fn subpath_with_positive_offset_and_len() {
+    let path = LtreePath::new("Top.Science.Astronomy.Astrophysics").unwrap();
+    let sub = path.subpath(1, 2);
+    assert_eq!(sub.labels().collect::<Vec<_>>(), vec!["Science", "Astronomy"]);
+}
+
+#[test]
+fn subpath_with_negative_offset() {
+    let path = LtreePath::new("Top.Science.Astronomy.Astrophysics").unwrap();
+    let sub = path.subpath(-2, 1);
+    assert_eq!(sub.labels().collect::<Vec<_>>(), vec!["Astronomy"]);
+}
+
+#[test]
+fn subpath_with_negative_len() {
+    let path = LtreePath::new("Top.Science.Astronomy.Astrophysics").unwrap();
+    let sub = path.subpath(0, -1);
+    assert_eq!(
+        sub.labels().collect::<Vec<_>>(),
+        vec!["Top", "Science", "Astronomy"]
+    );
+}
+
+#[test]
+fn subpath_never_panics_on_isize_min() {
+    let path = LtreePath::new("Top.Science").unwrap();
+    let sub = path.subpath(isize::MIN, 1);
+    assert_eq!(sub.labels().collect::<Vec<_>>(), vec!["Top"]);
+}
+
+#[test]
+fn subpath_from_never_panics_on_isize_min() {
+    let path = LtreePath::new("Top.Science").unwrap();
+    let sub = path.subpath_from(isize::MIN);
+    assert_eq!(sub.labels().collect::<Vec<_>>(), vec!["Top", "Science"]);
+}
+
+#[test]
+fn subpath_from_with_negative_offset() {
+    let path = LtreePath::new("Top.Science.Astronomy").unwrap();
+    let sub = path.subpath_from(-1);
+    assert_eq!(sub.labels().collect::<Vec<_>>(), vec!["Astronomy"]);
+}
+
+#[test]
+fn index_finds_a_subpath() {
+    let path = LtreePath::new("Top.Science.Astronomy.Astrophysics").unwrap();
+    let needle = LtreePath::new("Astronomy.Astrophysics").unwrap();
+    assert_eq!(path.index(&needle), Some(2));
+}
+
+#[test]
+fn index_returns_none_when_absent() {
+    let path = LtreePath::new("Top.Science").unwrap();
+    let needle = LtreePath::new("History").unwrap();
+    assert_eq!(path.index(&needle), None);
+}
+
+#[test]
+fn index_of_empty_subpath_is_zero() {
+    let path = LtreePath::new("Top.Science").unwrap();
+    let needle = LtreePath::new("").unwrap();
+    assert_eq!(path.index(&needle), Some(0));
+}
+
+#[test]
+fn lca_of_a_common_prefix() {
+    let a = LtreePath::new("Top.Science.Astronomy").unwrap();
+    let b = LtreePath::new("Top.Science.History").unwrap();
+    let lca = LtreePath::lca(&[a, b]);
+    assert_eq!(lca.labels().collect::<Vec<_>>(), vec!["Top", "Science"]);
+}
+
+#[test]
+fn lca_with_no_common_prefix_is_empty() {
+    let a = LtreePath::new("Top").unwrap();
+    let b = LtreePath::new("Other").unwrap();
+    let lca = LtreePath::lca(&[a, b]);
+    assert_eq!(lca.nlevel(), 0);
+}
+
+#[test]
+fn lca_of_an_empty_list_is_empty() {
+    let lca = LtreePath::lca(&[]);
+    assert_eq!(lca.nlevel(), 0);
+}
+
+#[test]
+fn concat_appends_labels() {
+    let a = LtreePath::new("Top.Science").unwrap();
+    let b = LtreePath::new("Astronomy").unwrap();
+    let joined = a.concat(&b);
+    assert_eq!(
+        joined.labels().collect::<Vec<_>>(),
+        vec!["Top", "Science", "Astronomy"]
+    );
+}
+
+#[test]
+fn display_round_trips_through_parse() {
+    let path = LtreePath::new("Top.Science.Astronomy").unwrap();
+    let round_tripped: LtreePath = path.to_string().parse().unwrap();
+    assert_eq!(path, round_tripped);
+}
+
+mod array_comparison {
+    use super::items::dsl::*;
+    use crate::values::Lquery;
+    use crate::{all, any};
+    use diesel::pg::Pg;
+    use diesel::prelude::*;
+
+    #[test]
+    fn any_renders_without_a_doubled_space() {
+        let query = items.filter(path.matches(any(vec![Lquery("Top.*".to_string())])));
+        let sql = diesel::debug_query::<Pg, _>(&query).to_string();
+
+        assert!(sql.contains("~ ANY($1)"), "{}", sql);
+        assert!(!sql.contains("  "), "{}", sql);
+    }
+
+    #[test]
+    fn all_renders_without_a_doubled_space() {
+        let query = items.filter(path.matches(all(vec![Lquery("Top.*".to_string())])));
+        let sql = diesel::debug_query::<Pg, _>(&query).to_string();
+
+        assert!(sql.contains("~ ALL($1)"), "{}", sql);
+        assert!(!sql.contains("  "), "{}", sql);
+    }
+}
+
+mod text_concat {
+    use super::items::dsl::*;
+    use crate::text_concat_ltree;
+    use diesel::pg::Pg;
+    use diesel::prelude::*;
+
+    #[test]
+    fn concat_text_renders_ltree_concat_text() {
+        let query = items.select(path.concat_text("Leaf"));
+        let sql = diesel::debug_query::<Pg, _>(&query).to_string();
+        assert!(sql.contains("\"items\".\"path\" || $1"), "{}", sql);
+    }
+
+    #[test]
+    fn text_concat_ltree_renders_text_concat_ltree() {
+        let query = items.select(text_concat_ltree("Leaf", path));
+        let sql = diesel::debug_query::<Pg, _>(&query).to_string();
+        assert!(sql.contains("$1 || \"items\".\"path\""), "{}", sql);
+    }
+}
+
+mod ordering {
+    use super::items::dsl::*;
+    use crate::values::Ltree;
+    use diesel::pg::Pg;
+    use diesel::prelude::*;
+
+    #[test]
+    fn lt_renders_a_plain_less_than() {
+        let query = items.filter(path.lt(Ltree("Top".to_string())));
+        let sql = diesel::debug_query::<Pg, _>(&query).to_string();
+        assert!(sql.contains("\"items\".\"path\" < $1"), "{}", sql);
+    }
+
+    #[test]
+    fn le_renders_a_plain_less_than_or_equal() {
+        let query = items.filter(path.le(Ltree("Top".to_string())));
+        let sql = diesel::debug_query::<Pg, _>(&query).to_string();
+        assert!(sql.contains("\"items\".\"path\" <= $1"), "{}", sql);
+    }
+
+    #[test]
+    fn gt_renders_a_plain_greater_than() {
+        let query = items.filter(path.gt(Ltree("Top".to_string())));
+        let sql = diesel::debug_query::<Pg, _>(&query).to_string();
+        assert!(sql.contains("\"items\".\"path\" > $1"), "{}", sql);
+    }
+
+    #[test]
+    fn ge_renders_a_plain_greater_than_or_equal() {
+        let query = items.filter(path.ge(Ltree("Top".to_string())));
+        let sql = diesel::debug_query::<Pg, _>(&query).to_string();
+        assert!(sql.contains("\"items\".\"path\" >= $1"), "{}", sql);
+    }
+}
+
+mod nullable {
+    use super::items::dsl::*;
+    use crate::values::{Lquery, Ltree, Ltxtquery};
+    use diesel::pg::Pg;
+    use diesel::prelude::*;
+
+    #[test]
+    fn optional_ltree_binds_as_a_nullable_parameter() {
+        let query = items.filter(path.nullable().eq(Some(Ltree("Top".to_string()))));
+        let sql = diesel::debug_query::<Pg, _>(&query).to_string();
+        assert!(sql.contains("\"items\".\"path\" = $1"), "{}", sql);
+    }
+
+    #[test]
+    fn optional_lquery_binds_as_a_nullable_parameter() {
+        let stmt = items.filter(query.nullable().eq(Some(Lquery("Top.*".to_string()))));
+        let sql = diesel::debug_query::<Pg, _>(&stmt).to_string();
+        assert!(sql.contains("\"items\".\"query\" = $1"), "{}", sql);
+    }
+
+    #[test]
+    fn optional_ltxtquery_binds_as_a_nullable_parameter() {
+        let stmt = items.filter(
+            tquery.nullable().eq(Some(Ltxtquery("Astronomy & Astrophysics".to_string()))),
+        );
+        let sql = diesel::debug_query::<Pg, _>(&stmt).to_string();
+        assert!(sql.contains("\"items\".\"tquery\" = $1"), "{}", sql);
+    }
+}